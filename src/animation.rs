@@ -0,0 +1,346 @@
+use std::fmt::Write;
+
+use bevy::animation::{animate_targets, AnimationClip, AnimationPlayer};
+use bevy::app::{App, Plugin, Update};
+use bevy::asset::{AssetEvent, AssetServer, Assets, Handle};
+use bevy::ecs::event::{EventReader, EventWriter};
+use bevy::ecs::query::Added;
+use bevy::ecs::system::{Commands, Query, Res, ResMut, Resource};
+use bevy::gltf::Gltf;
+use bevy::log;
+use bevy::prelude::{AnimationGraph, AnimationNodeIndex, Entity, IntoSystemConfigs};
+
+use crate::controls::ToggleGear;
+use crate::error::AppError;
+use crate::schedule::AppSet;
+use crate::AIRCRAFT_MODEL;
+
+/// Drives the aircraft model animations. Expects an [`Animations`] resource pointing at the loaded model, and does
+/// nothing without one.
+pub struct AircraftAnimationPlugin;
+
+impl Plugin for AircraftAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationRules>().add_systems(
+            Update,
+            (
+                build_animation_graph,
+                attach_animations.before(animate_targets),
+                control_land_gear_animation,
+            )
+                .in_set(AppSet::Animation),
+        );
+    }
+}
+
+/// Animations the app knows how to drive. Clips are matched to them by [`AnimationRules`] when a model loads, so
+/// models with a different number, order or naming of clips still map correctly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationKind {
+    LandingGear,
+}
+
+impl AnimationKind {
+    pub const ALL: [AnimationKind; 1] = [AnimationKind::LandingGear];
+}
+
+/// How a model's clip is picked for one [`AnimationKind`]. The first rule that finds a clip wins, in field order.
+#[derive(Clone, Debug, Default)]
+pub struct AnimationRule {
+    /// Exact clip name
+    pub clip: Option<String>,
+    /// Case-insensitive fragments searched for in clip names
+    pub name_contains: Vec<String>,
+    /// Asset path of one specific model and the index of its clip, for a model whose clip names match nothing else
+    pub model_index: Option<(String, usize)>,
+}
+
+impl AnimationRule {
+    fn find(&self, model_path: &str, clips: &[(Box<str>, AnimationNodeIndex)]) -> Option<AnimationNodeIndex> {
+        let by_name = || {
+            let clip = self.clip.as_deref()?;
+            clips.iter().find(|(name, _)| **name == *clip)
+        };
+        let by_fragment = || {
+            clips.iter().find(|(name, _)| {
+                let name = name.to_lowercase();
+                self.name_contains
+                    .iter()
+                    .any(|fragment| name.contains(&fragment.to_lowercase()))
+            })
+        };
+
+        let by_model_index = || {
+            let (path, index) = self.model_index.as_ref()?;
+            clips.get(*index).filter(|_| path == model_path)
+        };
+
+        by_name()
+            .or_else(by_fragment)
+            .or_else(by_model_index)
+            .map(|(_, node)| *node)
+    }
+}
+
+/// Clip matching rules per [`AnimationKind`], applied whenever a model finishes loading
+#[derive(Resource, Clone, Debug)]
+pub struct AnimationRules {
+    pub landing_gear: AnimationRule,
+}
+
+impl Default for AnimationRules {
+    fn default() -> Self {
+        Self {
+            landing_gear: AnimationRule {
+                clip: None,
+                name_contains: vec!["gear".to_string(), "chassis".to_string()],
+                // The bundled su-75 plays its gear with the first clip, which is what the app always played on G
+                model_index: Some((AIRCRAFT_MODEL.to_string(), 0)),
+            },
+        }
+    }
+}
+
+impl AnimationRules {
+    pub fn get(&self, kind: AnimationKind) -> &AnimationRule {
+        match kind {
+            AnimationKind::LandingGear => &self.landing_gear,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Animations {
+    gltf: Handle<Gltf>,
+    graph: Handle<AnimationGraph>,
+    /// Clip names and their graph nodes, in the order the clips appear in the model
+    clips: Vec<(Box<str>, AnimationNodeIndex)>,
+    /// Graph nodes matched to each kind by [`AnimationRules`]
+    kinds: Vec<(AnimationKind, AnimationNodeIndex)>,
+    /// Whether the next gear toggle plays the gear clip backwards. Kept here so a newly loaded model starts over.
+    gear_reversed: bool,
+    /// Whether the graph holds the clips of the currently loaded version of the model
+    graph_built: bool,
+}
+
+impl Animations {
+    /// Creates the registry for `gltf`. The graph stays empty until the model is loaded and its clips are known.
+    pub fn new(gltf: Handle<Gltf>, graphs: &mut Assets<AnimationGraph>) -> Self {
+        Self {
+            gltf,
+            graph: graphs.add(AnimationGraph::new()),
+            clips: Vec::new(),
+            kinds: Vec::new(),
+            gear_reversed: false,
+            graph_built: false,
+        }
+    }
+
+    /// Returns the graph node playing `kind`, or `None` if the model has no matching clip
+    pub fn get(&self, kind: AnimationKind) -> Option<AnimationNodeIndex> {
+        self.kinds
+            .iter()
+            .find(|(matched, _)| *matched == kind)
+            .map(|(_, node)| *node)
+    }
+}
+
+/// Fills the animation graph with every clip of the model, keyed by clip name, once the model is loaded. This covers a
+/// model that was already loaded when [`Animations`] was inserted, and fills the graph again when the model is
+/// reloaded.
+#[allow(clippy::too_many_arguments)]
+pub fn build_animation_graph(
+    mut events: EventReader<AssetEvent<Gltf>>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    rules: Res<AnimationRules>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    animations: Option<ResMut<Animations>>,
+    mut errors: EventWriter<AppError>,
+) {
+    let Some(mut animations) = animations else {
+        return;
+    };
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if *id == animations.gltf.id() {
+                animations.graph_built = false;
+            }
+        }
+    }
+    if animations.graph_built || !asset_server.is_loaded_with_dependencies(&animations.gltf) {
+        return;
+    }
+    let Some(gltf) = gltfs.get(&animations.gltf) else {
+        return;
+    };
+
+    let model_path = animations
+        .gltf
+        .path()
+        .map_or_else(|| "<unnamed>".to_string(), ToString::to_string);
+    let mut graph = AnimationGraph::new();
+    let clips: Vec<_> = gltf
+        .animations
+        .iter()
+        .enumerate()
+        .map(|(index, clip)| {
+            let name = gltf
+                .named_animations
+                .iter()
+                .find(|(_, named)| *named == clip)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| format!("Animation{index}").into());
+            (name, graph.add_clip(clip.clone(), 1.0, graph.root))
+        })
+        .collect();
+
+    animations.kinds = AnimationKind::ALL
+        .into_iter()
+        .filter_map(|kind| Some((kind, rules.get(kind).find(&model_path, &clips)?)))
+        .collect();
+    graphs.insert(&animations.graph, graph);
+    animations.clips = clips;
+    animations.graph_built = true;
+
+    log_model_report(gltf, &animations, &animation_clips);
+    for kind in AnimationKind::ALL {
+        if animations.get(kind).is_none() {
+            errors.send(AppError::UnmatchedAnimation {
+                model: model_path.clone(),
+                kind,
+            });
+        }
+    }
+}
+
+/// Logs the scenes, nodes and animation clips of a freshly loaded model
+fn log_model_report(gltf: &Gltf, animations: &Animations, animation_clips: &Assets<AnimationClip>) {
+    let sorted_names = |names: Vec<&str>| {
+        let mut names = names;
+        names.sort_unstable();
+        names.join(", ")
+    };
+    let path = animations
+        .gltf
+        .path()
+        .map_or_else(|| "<unnamed>".to_string(), ToString::to_string);
+
+    let mut report = format!("Loaded model {path}");
+    let _ = write!(
+        report,
+        "\n  {} scenes: {}",
+        gltf.scenes.len(),
+        sorted_names(gltf.named_scenes.keys().map(AsRef::as_ref).collect())
+    );
+    let _ = write!(
+        report,
+        "\n  {} nodes: {}",
+        gltf.nodes.len(),
+        sorted_names(gltf.named_nodes.keys().map(AsRef::as_ref).collect())
+    );
+    let _ = write!(report, "\n  {} animation clips:", animations.clips.len());
+    for (handle, (name, node)) in gltf.animations.iter().zip(&animations.clips) {
+        let _ = write!(report, "\n    {name}");
+        if let Some(clip) = animation_clips.get(handle) {
+            let _ = write!(report, ": {:.2}s, {} targets", clip.duration(), clip.curves().len());
+        }
+        for kind in AnimationKind::ALL {
+            if animations.get(kind) == Some(*node) {
+                let _ = write!(report, " -> {kind:?}");
+            }
+        }
+    }
+    log::info!("{report}");
+}
+
+/// Attaches the animation graph to the scene
+pub fn attach_animations(
+    mut commands: Commands,
+    to_animated_entities: Query<(Entity, &AnimationPlayer), Added<AnimationPlayer>>,
+    animations: Option<Res<Animations>>,
+) {
+    let Some(animations) = animations else {
+        return;
+    };
+    for (entity, _player) in &to_animated_entities {
+        log::info!("Attaching animations");
+        commands.entity(entity).insert(animations.graph.clone());
+    }
+}
+
+pub fn control_land_gear_animation(
+    mut toggle_gear: EventReader<ToggleGear>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+    animations: Option<ResMut<Animations>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+) {
+    let Some(mut animations) = animations else {
+        return;
+    };
+    for _ in toggle_gear.read() {
+        let Some(animation_graph) = animation_graphs.get(&animations.graph) else {
+            return;
+        };
+
+        let gear = animations.get(AnimationKind::LandingGear);
+        for (node_index, mut player) in gear.into_iter().zip(&mut animation_players) {
+            let animation_node = &animation_graph[node_index];
+            let animation_start_time = if animations.gear_reversed {
+                animation_node
+                    .clip
+                    .as_ref()
+                    .and_then(|clip_handle| animation_clips.get(clip_handle).map(|clip| clip.duration()))
+                    .unwrap_or_default()
+            } else {
+                0.0
+            };
+
+            if player.all_finished() {
+                for (_, playing_animation) in player.playing_animations_mut() {
+                    playing_animation.replay();
+                }
+                player.seek_all_by(animation_start_time);
+            }
+            player.adjust_speeds(-1.0);
+            player.play(node_index);
+        }
+        animations.gear_reversed = !animations.gear_reversed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::AnimationNodeIndex;
+
+    use super::AnimationRules;
+    use crate::AIRCRAFT_MODEL;
+
+    fn clips(names: &[&str]) -> Vec<(Box<str>, AnimationNodeIndex)> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| ((*name).into(), AnimationNodeIndex::new(index)))
+            .collect()
+    }
+
+    #[test]
+    fn gear_matches_by_fragment_before_index() {
+        let rule = AnimationRules::default().landing_gear;
+        let clips = clips(&["Canopy", "LandingGearRetract"]);
+
+        assert_eq!(rule.find("other/plane.glb", &clips), Some(AnimationNodeIndex::new(1)));
+        assert_eq!(rule.find(AIRCRAFT_MODEL, &clips), Some(AnimationNodeIndex::new(1)));
+    }
+
+    #[test]
+    fn first_clip_fallback_only_applies_to_the_bundled_model() {
+        let rule = AnimationRules::default().landing_gear;
+        let clips = clips(&["Animation0", "Canopy"]);
+
+        assert_eq!(rule.find(AIRCRAFT_MODEL, &clips), Some(AnimationNodeIndex::new(0)));
+        assert_eq!(rule.find("other/plane.glb", &clips), None);
+    }
+}
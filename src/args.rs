@@ -0,0 +1,431 @@
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseFloatError;
+use std::str::FromStr;
+
+use bevy::math::Vec3;
+
+use crate::animation::AnimationRules;
+use crate::camera::attract::AttractSettings;
+use crate::camera::turntable::TurntableSettings;
+use crate::camera::CameraSettings;
+use crate::environment::SunSettings;
+use crate::error::AppError;
+use crate::AIRCRAFT_MODEL;
+
+/// Settings picked on the command line. Anything not given, or given with an invalid value, keeps its default and
+/// the problem is recorded in `errors`.
+pub struct Options {
+    pub camera: CameraSettings,
+    pub ui_scale: f32,
+    pub animation_rules: AnimationRules,
+    pub attract: AttractSettings,
+    pub turntable: TurntableSettings,
+    pub sun: SunSettings,
+    pub kiosk: bool,
+    /// Model the aircraft is spawned with. Always [`AIRCRAFT_MODEL`] from the command line, tests point it elsewhere
+    /// to pin down whether the model is found.
+    pub aircraft_model: String,
+    /// Problems with the command line, to be reported once the app runs
+    pub errors: Vec<AppError>,
+}
+
+impl Options {
+    /// Parses `args`, which must not include the program name
+    pub fn parse(args: &[String]) -> Self {
+        let mut args = Args::new(args);
+
+        // `--camera panorbit|simple`, `--fov <degrees>`, `--near <m>` and `--far <m>` override the camera settings.
+        // `--orbit-inertia` keeps the orbit camera spinning after a flick, slowing down by `--orbit-damping <1/s>`,
+        // and `--pinch-zoom-speed <factor>` and `--gesture-pan-speed <factor>` tune touchpad gestures.
+        let camera_defaults = CameraSettings::default();
+        let fov = args.checked(
+            "--fov",
+            camera_defaults.fov,
+            "must be between 0 and 180 degrees",
+            |fov| *fov > 0.0 && *fov < 180.0,
+        );
+        let near = args.checked("--near", camera_defaults.near, "must be positive", |near| *near > 0.0);
+        let far = args.checked("--far", camera_defaults.far, "must be beyond --near", |far| *far > near);
+        // A `--near` beyond the default far plane is only caught once `--far` is known
+        let near = if near < far {
+            near
+        } else {
+            args.reject("--near", near, "must be closer than --far");
+            camera_defaults.near
+        };
+        let camera = CameraSettings {
+            controller: args.value("--camera").unwrap_or(camera_defaults.controller),
+            fov,
+            near,
+            far,
+            orbit_inertia: args.flag("--orbit-inertia"),
+            orbit_damping: args.checked(
+                "--orbit-damping",
+                camera_defaults.orbit_damping,
+                "must be finite and not negative",
+                |damping| damping.is_finite() && *damping >= 0.0,
+            ),
+            pinch_zoom_speed: args.checked(
+                "--pinch-zoom-speed",
+                camera_defaults.pinch_zoom_speed,
+                "must be finite and positive",
+                |speed| speed.is_finite() && *speed > 0.0,
+            ),
+            gesture_pan_speed: args.checked(
+                "--gesture-pan-speed",
+                camera_defaults.gesture_pan_speed,
+                "must be finite and positive",
+                |speed| speed.is_finite() && *speed > 0.0,
+            ),
+        };
+
+        // `--ui-scale <factor>` scales all UI on top of the display scale factor Bevy picks up from the OS
+        let ui_scale = args.checked("--ui-scale", 1.0, "must be positive", |scale| *scale > 0.0);
+
+        // `--gear-clip <name>` picks the landing gear clip by name for models the default rules don't match.
+        // `--gear-clip-contains <fragment>`, repeatable, replaces the name fragments searched for otherwise.
+        let mut animation_rules = AnimationRules::default();
+        animation_rules.landing_gear.clip = args.value("--gear-clip");
+        let fragments: Vec<String> = args.values("--gear-clip-contains");
+        if !fragments.is_empty() {
+            animation_rules.landing_gear.name_contains = fragments;
+        }
+
+        // `--attract-timeout <s>` sets how long the app waits without input before the camera starts orbiting by
+        // itself
+        let attract_defaults = AttractSettings::default();
+        let attract = AttractSettings {
+            idle_timeout: args.checked(
+                "--attract-timeout",
+                attract_defaults.idle_timeout,
+                "must not be negative",
+                |timeout| *timeout >= 0.0,
+            ),
+            ..attract_defaults
+        };
+
+        // `--turntable-duration <s>` and `--turntable-frames <n>` set how long a turntable capture takes and how
+        // many screenshots it saves
+        let turntable_defaults = TurntableSettings::default();
+        let turntable = TurntableSettings {
+            duration: args.checked(
+                "--turntable-duration",
+                turntable_defaults.duration,
+                "must be positive",
+                |duration| *duration > 0.0,
+            ),
+            frames: args.checked(
+                "--turntable-frames",
+                turntable_defaults.frames,
+                "must be positive",
+                |frames| *frames > 0,
+            ),
+            ..turntable_defaults
+        };
+
+        // `--sun-speed <rad/s>` turns the sun around `--sun-axis <x,y,z>`
+        let sun_defaults = SunSettings::default();
+        let sun = SunSettings {
+            rotation_speed: args.checked("--sun-speed", sun_defaults.rotation_speed, "must be finite", |speed| {
+                speed.is_finite()
+            }),
+            axis: args
+                .checked("--sun-axis", ArgVec3(sun_defaults.axis), "must not be zero", |axis| {
+                    axis.0 != Vec3::ZERO
+                })
+                .0,
+        };
+
+        // `--kiosk` is for unattended exhibition machines: no quitting with Esc, no perf overlay, no turntable
+        // captures filling the disk and no swapping the aircraft by dropping files
+        let kiosk = args.flag("--kiosk");
+
+        Self {
+            camera,
+            ui_scale,
+            animation_rules,
+            attract,
+            turntable,
+            sun,
+            kiosk,
+            aircraft_model: AIRCRAFT_MODEL.to_string(),
+            errors: args.finish(),
+        }
+    }
+}
+
+/// Command line arguments being looked up, remembering which flags exist and what went wrong
+struct Args<'a> {
+    args: &'a [String],
+    known: Vec<&'static str>,
+    errors: Vec<AppError>,
+}
+
+impl<'a> Args<'a> {
+    fn new(args: &'a [String]) -> Self {
+        Self {
+            args,
+            known: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Returns whether the switch `flag` was given
+    fn flag(&mut self, flag: &'static str) -> bool {
+        self.known.push(flag);
+        self.args.iter().any(|arg| arg == flag)
+    }
+
+    /// Returns the value following `flag`. A missing or unparsable value is recorded and treated as absent.
+    fn value<T>(&mut self, flag: &'static str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.known.push(flag);
+        let mut rest = self.args.iter().skip_while(|arg| *arg != flag);
+        rest.next()?;
+        let Some(value) = rest.next().filter(|value| !value.starts_with("--")) else {
+            self.reject(flag, "", "missing value, using the default");
+            return None;
+        };
+
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.reject(flag, value, err);
+                None
+            },
+        }
+    }
+
+    /// Returns the values following every occurrence of the repeatable `flag`, skipping missing or unparsable ones
+    fn values<T>(&mut self, flag: &'static str) -> Vec<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.known.push(flag);
+        let mut values = Vec::new();
+        for (index, _) in self.args.iter().enumerate().filter(|(_, arg)| *arg == flag) {
+            let Some(value) = self.args.get(index + 1).filter(|value| !value.starts_with("--")) else {
+                self.reject(flag, "", "missing value, ignored");
+                continue;
+            };
+            match value.parse() {
+                Ok(value) => values.push(value),
+                Err(err) => self.reject(flag, value, err),
+            }
+        }
+        values
+    }
+
+    /// Returns the value following `flag` if it passes `valid`, otherwise records why not and returns `default`
+    fn checked<T>(&mut self, flag: &'static str, default: T, reason: &str, valid: impl FnOnce(&T) -> bool) -> T
+    where
+        T: FromStr + Display,
+        T::Err: Display,
+    {
+        match self.value(flag) {
+            Some(value) if valid(&value) => value,
+            Some(value) => {
+                self.reject(flag, value, reason);
+                default
+            },
+            None => default,
+        }
+    }
+
+    fn reject(&mut self, flag: &str, value: impl Display, reason: impl Display) {
+        self.errors.push(AppError::InvalidArgument {
+            flag: flag.to_string(),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Reports flags none of the lookups asked for and returns every problem found
+    fn finish(mut self) -> Vec<AppError> {
+        let unknown: Vec<_> = self
+            .args
+            .iter()
+            .filter(|arg| arg.starts_with("--") && !self.known.contains(&arg.as_str()))
+            .collect();
+        for flag in unknown {
+            self.reject(flag, "", "unknown flag, ignored");
+        }
+        self.errors
+    }
+}
+
+/// Command line vector written as `x,y,z`
+struct ArgVec3(Vec3);
+
+impl FromStr for ArgVec3 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components: Vec<f32> = s
+            .split(',')
+            .map(|component| component.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|err: ParseFloatError| err.to_string())?;
+        match components[..] {
+            [x, y, z] => Ok(ArgVec3(Vec3::new(x, y, z))),
+            _ => Err("expected `x,y,z`".to_string()),
+        }
+    }
+}
+
+impl Display for ArgVec3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.0.x, self.0.y, self.0.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec3;
+
+    use super::Options;
+    use crate::camera::CameraController;
+    use crate::error::AppError;
+
+    fn parse(args: &[&str]) -> Options {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        Options::parse(&args)
+    }
+
+    /// Flags of the recorded errors, in order
+    fn rejected(options: &Options) -> Vec<&str> {
+        options
+            .errors
+            .iter()
+            .map(|err| match err {
+                AppError::InvalidArgument { flag, .. } => flag.as_str(),
+                other => panic!("unexpected error {other}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let options = parse(&[]);
+
+        assert!(options.errors.is_empty());
+        assert_eq!(options.camera.fov, 45.0);
+        assert_eq!(options.ui_scale, 1.0);
+        assert!(!options.kiosk);
+    }
+
+    #[test]
+    fn values_and_switches() {
+        let options = parse(&[
+            "--camera",
+            "simple",
+            "--fov",
+            "60",
+            "--kiosk",
+            "--sun-axis",
+            "1, 0,0",
+            "--turntable-frames",
+            "12",
+        ]);
+
+        assert!(options.errors.is_empty(), "{:?}", options.errors);
+        assert_eq!(options.camera.controller, CameraController::Simple);
+        assert_eq!(options.camera.fov, 60.0);
+        assert!(options.kiosk);
+        assert_eq!(options.sun.axis, Vec3::X);
+        assert_eq!(options.turntable.frames, 12);
+    }
+
+    #[test]
+    fn unparsable_values_keep_the_default() {
+        let options = parse(&["--fov", "wide", "--sun-axis", "1,0", "--camera", "drone"]);
+
+        assert_eq!(rejected(&options), ["--fov", "--camera", "--sun-axis"]);
+        assert_eq!(options.camera.fov, 45.0);
+        assert_eq!(options.sun.axis, Vec3::Y);
+    }
+
+    #[test]
+    fn out_of_range_values_keep_the_default() {
+        let options = parse(&[
+            "--fov",
+            "200",
+            "--ui-scale",
+            "0",
+            "--turntable-frames",
+            "0",
+            "--sun-speed",
+            "NaN",
+        ]);
+
+        assert_eq!(rejected(&options), [
+            "--fov",
+            "--ui-scale",
+            "--turntable-frames",
+            "--sun-speed"
+        ]);
+        assert_eq!(options.camera.fov, 45.0);
+        assert_eq!(options.ui_scale, 1.0);
+        assert_eq!(options.turntable.frames, 36);
+        assert_eq!(options.sun.rotation_speed, 0.0);
+    }
+
+    #[test]
+    fn camera_feel_values_are_checked() {
+        let options = parse(&[
+            "--orbit-damping",
+            "-1",
+            "--pinch-zoom-speed",
+            "NaN",
+            "--gesture-pan-speed",
+            "0",
+        ]);
+        assert_eq!(rejected(&options), [
+            "--orbit-damping",
+            "--pinch-zoom-speed",
+            "--gesture-pan-speed"
+        ]);
+        assert_eq!(options.camera.orbit_damping, 4.0);
+
+        let options = parse(&["--orbit-damping", "0", "--pinch-zoom-speed", "2.5"]);
+        assert!(options.errors.is_empty());
+        assert_eq!(options.camera.orbit_damping, 0.0);
+        assert_eq!(options.camera.pinch_zoom_speed, 2.5);
+    }
+
+    #[test]
+    fn repeated_gear_fragments_replace_the_defaults() {
+        let options = parse(&["--gear-clip-contains", "Fahrwerk", "--gear-clip-contains", "wheels"]);
+        assert!(options.errors.is_empty());
+        assert_eq!(options.animation_rules.landing_gear.name_contains, [
+            "Fahrwerk", "wheels"
+        ]);
+
+        let options = parse(&[]);
+        assert_eq!(options.animation_rules.landing_gear.name_contains, ["gear", "chassis"]);
+    }
+
+    #[test]
+    fn clipping_planes_must_be_ordered() {
+        let far_before_near = parse(&["--near", "5", "--far", "2"]);
+        assert_eq!(rejected(&far_before_near), ["--far"]);
+        assert_eq!((far_before_near.camera.near, far_before_near.camera.far), (5.0, 1000.0));
+
+        let near_beyond_default_far = parse(&["--near", "2000"]);
+        assert_eq!(rejected(&near_beyond_default_far), ["--near"]);
+        assert_eq!(near_beyond_default_far.camera.near, 0.1);
+    }
+
+    #[test]
+    fn unknown_flags_and_missing_values_are_reported() {
+        let options = parse(&["--fvo", "60", "--gear-clip", "--kiosk", "--far"]);
+
+        assert_eq!(rejected(&options), ["--far", "--gear-clip", "--fvo"]);
+        assert_eq!(options.animation_rules.landing_gear.clip, None);
+        assert!(options.kiosk);
+    }
+}
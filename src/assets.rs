@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::AssetPlugin;
+
+/// Folder the default asset source reads from
+pub fn asset_root() -> PathBuf {
+    FileAssetReader::new(AssetPlugin::default().file_path)
+        .root_path()
+        .to_path_buf()
+}
+
+/// Checks that every path in `paths` exists in the asset folder. Returns the missing paths so callers can report them
+/// and fall back to placeholders.
+pub fn check_assets<'a>(paths: &[&'a str]) -> Vec<&'a str> {
+    let root = asset_root();
+    paths
+        .iter()
+        .copied()
+        .filter(|path| !root.join(path).is_file())
+        .collect()
+}
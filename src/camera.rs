@@ -1,2 +1,99 @@
+use std::str::FromStr;
+
+use bevy::app::{App, Plugin};
+use bevy::ecs::system::Resource;
+use bevy::prelude::default;
+use bevy::render::camera::PerspectiveProjection;
+use panorbit::PanOrbitCameraPlugin;
+use simple::SimpleCameraPlugin;
+
+pub mod attract;
 pub mod panorbit;
 pub mod simple;
+pub mod turntable;
+
+/// Camera controllers the app can be started with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraController {
+    #[default]
+    PanOrbit,
+    Simple,
+}
+
+impl FromStr for CameraController {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panorbit" => Ok(CameraController::PanOrbit),
+            "simple" => Ok(CameraController::Simple),
+            _ => Err("expected `panorbit` or `simple`".to_string()),
+        }
+    }
+}
+
+/// Camera configuration, available to the controllers as a resource
+#[derive(Resource, Clone, Debug)]
+pub struct CameraSettings {
+    pub controller: CameraController,
+    /// Vertical field of view in degrees
+    pub fov: f32,
+    /// Near clipping plane. Raising it gives distant geometry more depth precision and reduces z-fighting.
+    pub near: f32,
+    /// Far clipping plane, geometry beyond it is culled
+    pub far: f32,
+    /// Keep the orbit camera spinning after the orbit button is released mid-drag
+    pub orbit_inertia: bool,
+    /// How fast an inertial spin slows down, as an exponential decay rate per second
+    pub orbit_damping: f32,
+    /// Zoom speed of touchpad pinch gestures, at 5 the distance to the focus changes by as much as the pinch
+    pub pinch_zoom_speed: f32,
+    /// Speed of two-finger touchpad pans, relative to panning with the mouse
+    pub gesture_pan_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            controller: CameraController::default(),
+            fov: 45.0,
+            near: 0.1,
+            far: 1000.0,
+            orbit_inertia: false,
+            orbit_damping: 4.0,
+            pinch_zoom_speed: 5.0,
+            gesture_pan_speed: 1.0,
+        }
+    }
+}
+
+impl CameraSettings {
+    pub fn projection(&self) -> PerspectiveProjection {
+        PerspectiveProjection {
+            fov: self.fov.to_radians(),
+            near: self.near,
+            far: self.far,
+            ..default()
+        }
+    }
+}
+
+/// Spawns the camera and installs the selected controller
+pub struct CameraPlugin {
+    pub settings: CameraSettings,
+    /// Whether the pan-orbit controller may capture turntable screenshots
+    pub turntable: bool,
+}
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone());
+
+        match self.settings.controller {
+            CameraController::PanOrbit => app.add_plugins(PanOrbitCameraPlugin {
+                turntable: self.turntable,
+            }),
+            CameraController::Simple => app.add_plugins(SimpleCameraPlugin),
+        };
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::ecs::event::EventReader;
+use bevy::ecs::system::{Query, Res, ResMut, Resource};
+use bevy::input::keyboard::KeyCode;
+use bevy::input::mouse::{MouseButton, MouseMotion, MouseWheel};
+use bevy::input::ButtonInput;
+use bevy::log;
+use bevy::math::Quat;
+use bevy::prelude::IntoSystemConfigs;
+use bevy::time::Time;
+use bevy::transform::components::Transform;
+
+use super::panorbit::PanOrbitCamera;
+use crate::schedule::AppSet;
+
+/// Slowly orbits the [`PanOrbitCamera`] around the aircraft after a period without input, until the next key press
+/// or mouse input
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttractSettings>()
+            .init_resource::<AttractMode>()
+            .add_systems(Update, track_idle.in_set(AppSet::Input))
+            .add_systems(Update, orbit.in_set(AppSet::CameraFollow));
+    }
+}
+
+#[derive(Resource)]
+pub struct AttractSettings {
+    /// Seconds without input before attract mode starts
+    pub idle_timeout: f32,
+    /// Orbit speed in radians per second
+    pub orbit_speed: f32,
+}
+
+impl Default for AttractSettings {
+    fn default() -> Self {
+        Self {
+            idle_timeout: 180.0,
+            orbit_speed: 0.15,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct AttractMode {
+    /// Seconds since the last input
+    pub idle: f32,
+    pub active: bool,
+}
+
+fn track_idle(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut scroll_events: EventReader<MouseWheel>,
+    settings: Res<AttractSettings>,
+    mut attract: ResMut<AttractMode>,
+) {
+    let any_input = keys.get_pressed().next().is_some()
+        || buttons.get_pressed().next().is_some()
+        || motion_events.read().count() > 0
+        || scroll_events.read().count() > 0;
+
+    if any_input {
+        if attract.active {
+            log::info!("Leaving attract mode");
+        }
+        *attract = AttractMode::default();
+        return;
+    }
+
+    attract.idle += time.delta_seconds();
+    if !attract.active && attract.idle >= settings.idle_timeout {
+        log::info!("No input for {:.0}s, starting attract mode", attract.idle);
+        attract.active = true;
+    }
+}
+
+pub fn orbit(
+    time: Res<Time>,
+    settings: Res<AttractSettings>,
+    attract: Res<AttractMode>,
+    mut cameras: Query<(&PanOrbitCamera, &mut Transform)>,
+) {
+    if !attract.active {
+        return;
+    }
+
+    let yaw = Quat::from_rotation_y(settings.orbit_speed * time.delta_seconds());
+    for (camera, mut transform) in &mut cameras {
+        transform.rotation = yaw * transform.rotation;
+        camera.place(&mut transform);
+    }
+}
@@ -5,19 +5,39 @@ use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::ecs::component::Component;
 use bevy::ecs::event::EventReader;
 use bevy::ecs::system::{Commands, Query, Res};
+use bevy::input::gestures::{PanGesture, PinchGesture};
 use bevy::input::mouse::{MouseButton, MouseMotion, MouseWheel};
 use bevy::input::ButtonInput;
 use bevy::math::{Mat3, Quat, Vec2, Vec3};
-use bevy::prelude::default;
-use bevy::render::camera::{Camera, PerspectiveProjection, Projection};
+use bevy::prelude::{default, IntoSystemConfigs};
+use bevy::render::camera::{Camera, Projection};
+use bevy::time::Time;
 use bevy::transform::components::Transform;
 use bevy::window::Window;
 
-pub struct PanOrbitCameraPlugin;
+use super::attract::AttractPlugin;
+use super::turntable::TurntablePlugin;
+use super::CameraSettings;
+use crate::schedule::AppSet;
+
+pub struct PanOrbitCameraPlugin {
+    /// Whether the turntable screenshot sequence can be started
+    pub turntable: bool,
+}
+
+/// How long the orbit button has to be held without moving before an inertial spin is dropped. Shorter gaps are
+/// frames rendered between two mouse polls.
+const SPIN_HOLD_THRESHOLD: f32 = 0.08;
 
 impl Plugin for PanOrbitCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn).add_systems(Update, update_input);
+        app.add_plugins(AttractPlugin)
+            .add_systems(Startup, spawn)
+            .add_systems(Update, update_input.in_set(AppSet::CameraFollow));
+
+        if self.turntable {
+            app.add_plugins(TurntablePlugin);
+        }
     }
 }
 
@@ -29,6 +49,21 @@ pub struct PanOrbitCamera {
     pub upside_down: bool,
     pub orbit_button: MouseButton,
     pub pan_button: MouseButton,
+    /// Yaw and pitch speed in radians per second, kept after a flick when [`CameraSettings::orbit_inertia`] is on
+    pub spin: Vec2,
+    /// Seconds since the last orbit drag motion
+    pub since_motion: f32,
+}
+
+impl PanOrbitCamera {
+    /// Moves `transform` `radius` away from `focus`, so that with its current rotation it looks at the focus point.
+    /// Orbiting rotates the transform around the global y axis and then calls this, which makes the yaw behave like
+    /// a turntable.
+    pub fn place(&self, transform: &mut Transform) {
+        // emulating parent/child: parent = x and y rotation, child = z-offset
+        let rot_matrix = Mat3::from_quat(transform.rotation);
+        transform.translation = self.focus + rot_matrix.mul_vec3(Vec3::new(0.0, 0.0, self.radius));
+    }
 }
 
 impl Default for PanOrbitCamera {
@@ -39,11 +74,13 @@ impl Default for PanOrbitCamera {
             upside_down: false,
             orbit_button: MouseButton::Left,
             pan_button: MouseButton::Right,
+            spin: Vec2::ZERO,
+            since_motion: 0.0,
         }
     }
 }
 
-pub fn spawn(mut commands: Commands) {
+pub fn spawn(mut commands: Commands, settings: Res<CameraSettings>) {
     let translation = Vec3::new(-3.0, 5.0, 15.0);
     let radius = translation.length();
 
@@ -52,11 +89,7 @@ pub fn spawn(mut commands: Commands) {
         Camera3dBundle {
             camera: Camera { hdr: true, ..default() },
             tonemapping: Tonemapping::BlenderFilmic,
-            projection: PerspectiveProjection {
-                fov: 45.0_f32.to_radians(),
-                ..default()
-            }
-            .into(),
+            projection: settings.projection().into(),
             transform: Transform::from_translation(translation).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
         },
@@ -65,14 +98,23 @@ pub fn spawn(mut commands: Commands) {
 }
 
 /// Pan the camera with middle mouse click, zoom with scroll wheel, orbit with right mouse click.
+/// Touchpads can also zoom with a pinch and pan with two fingers.
+#[allow(clippy::too_many_arguments)]
 pub fn update_input(
     windows: Query<&Window>,
     mut motion_events: EventReader<MouseMotion>,
     mut scroll_events: EventReader<MouseWheel>,
+    mut pinch_events: EventReader<PinchGesture>,
+    mut pan_gesture_events: EventReader<PanGesture>,
     input_mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
     mut query: Query<(&mut PanOrbitCamera, &mut Transform, &Projection)>,
 ) {
-    let primary_window = windows.single();
+    // The window is gone for a frame or two while the app shuts down, there is nothing to orbit in then
+    let Ok(primary_window) = windows.get_single() else {
+        return;
+    };
 
     for (mut camera, mut transform, projection) in query.iter_mut() {
         let mut pan = Vec2::ZERO;
@@ -90,9 +132,15 @@ pub fn update_input(
                 pan += motion.delta;
             }
         }
+        for gesture in pan_gesture_events.read() {
+            pan += gesture.0 * settings.gesture_pan_speed;
+        }
         for wheel in scroll_events.read() {
             scroll += wheel.y;
         }
+        for pinch in pinch_events.read() {
+            scroll += pinch.0 * settings.pinch_zoom_speed;
+        }
         if input_mouse.just_released(camera.orbit_button) || input_mouse.just_pressed(camera.orbit_button) {
             orbit_button_changed = true;
         }
@@ -106,8 +154,12 @@ pub fn update_input(
         }
 
         let mut any = false;
+        let dt = time.delta_seconds();
+        let mut rotation = Vec2::ZERO;
+        if input_mouse.just_pressed(camera.orbit_button) {
+            camera.since_motion = 0.0;
+        }
         if rotation_move.length_squared() > 0.0 {
-            any = true;
             let window = get_window_size(primary_window);
             let delta_x = {
                 let delta = rotation_move.x / window.x * std::f32::consts::PI * 2.0;
@@ -118,11 +170,37 @@ pub fn update_input(
                 }
             };
             let delta_y = rotation_move.y / window.y * std::f32::consts::PI;
-            let yaw = Quat::from_rotation_y(-delta_x);
-            let pitch = Quat::from_rotation_x(-delta_y);
+            rotation = Vec2::new(delta_x, delta_y);
+            // The motion covers every frame since the previous one, which is more than one frame when the mouse is
+            // polled less often than frames are rendered
+            let elapsed = camera.since_motion + dt;
+            if elapsed > 0.0 {
+                // Smoothed over a few frames, a single frame of mouse motion is too jittery to spin from
+                camera.spin = camera.spin.lerp(rotation / elapsed, 0.5);
+            }
+            camera.since_motion = 0.0;
+        } else if input_mouse.pressed(camera.orbit_button) {
+            // Holding the orbit button still stops the spin
+            camera.since_motion += dt;
+            if camera.since_motion >= SPIN_HOLD_THRESHOLD {
+                camera.spin = Vec2::ZERO;
+            }
+        } else if settings.orbit_inertia && camera.spin != Vec2::ZERO {
+            rotation = camera.spin * dt;
+            camera.spin *= (-settings.orbit_damping * dt).exp();
+            if camera.spin.length_squared() < 1e-6 {
+                camera.spin = Vec2::ZERO;
+            }
+        }
+
+        if rotation != Vec2::ZERO {
+            any = true;
+            let yaw = Quat::from_rotation_y(-rotation.x);
+            let pitch = Quat::from_rotation_x(-rotation.y);
             transform.rotation = yaw * transform.rotation; // rotate around global y axis
             transform.rotation = transform.rotation * pitch; // rotate around local x axis
-        } else if pan.length_squared() > 0.0 {
+        }
+        if pan.length_squared() > 0.0 {
             any = true;
             // make panning distance independent of resolution and FOV,
             let window = get_window_size(primary_window);
@@ -135,7 +213,9 @@ pub fn update_input(
             // make panning proportional to distance away from focus point
             let translation = (right + up) * camera.radius;
             camera.focus += translation;
-        } else if scroll.abs() > 0.0 {
+        }
+        // Not exclusive with panning, touchpads send pinch and pan gestures in the same frame
+        if scroll.abs() > 0.0 {
             any = true;
             camera.radius -= scroll * camera.radius * 0.2;
             // dont allow zoom to reach zero or you get stuck
@@ -143,11 +223,7 @@ pub fn update_input(
         }
 
         if any {
-            // emulating parent/child to make the yaw/y-axis rotation behave like a turntable
-            // parent = x and y rotation
-            // child = z-offset
-            let rot_matrix = Mat3::from_quat(transform.rotation);
-            transform.translation = camera.focus + rot_matrix.mul_vec3(Vec3::new(0.0, 0.0, camera.radius));
+            camera.place(&mut transform);
         }
     }
 
@@ -6,16 +6,20 @@ use bevy::ecs::system::{Commands, Query, Res};
 use bevy::input::mouse::{MouseButton, MouseMotion, MouseWheel};
 use bevy::input::ButtonInput;
 use bevy::math::{EulerRot, Quat, Vec3};
-use bevy::prelude::default;
+use bevy::prelude::{default, IntoSystemConfigs};
 use bevy::render::camera::Camera;
 use bevy::time::Time;
 use bevy::transform::components::Transform;
 
+use super::CameraSettings;
+use crate::schedule::AppSet;
+
 pub struct SimpleCameraPlugin;
 
 impl Plugin for SimpleCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn).add_systems(Update, update_input);
+        app.add_systems(Startup, spawn)
+            .add_systems(Update, update_input.in_set(AppSet::CameraFollow));
     }
 }
 
@@ -34,11 +38,12 @@ impl Default for SimpleCamera {
     }
 }
 
-pub fn spawn(mut commands: Commands) {
+pub fn spawn(mut commands: Commands, settings: Res<CameraSettings>) {
     let translation = Vec3::new(0.7, 20.0, 40.0);
 
     commands.spawn((SimpleCamera::default(), Camera3dBundle {
         camera: Camera { hdr: true, ..default() },
+        projection: settings.projection().into(),
         transform: Transform::from_translation(translation).looking_at(Vec3::ZERO, Vec3::Y),
         ..default()
     }));
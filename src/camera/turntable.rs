@@ -0,0 +1,145 @@
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::system::{Commands, Query, Res, ResMut, Resource};
+use bevy::input::keyboard::KeyCode;
+use bevy::input::ButtonInput;
+use bevy::log;
+use bevy::math::Quat;
+use bevy::prelude::IntoSystemConfigs;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::time::Time;
+use bevy::transform::components::Transform;
+use bevy::window::PrimaryWindow;
+
+use super::attract;
+use super::panorbit::{update_input, PanOrbitCamera};
+use crate::schedule::AppSet;
+
+/// Orbits the [`PanOrbitCamera`] once around its focus point while saving screenshots at evenly spaced angles
+pub struct TurntablePlugin;
+
+impl Plugin for TurntablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TurntableSettings>()
+            .add_systems(Update, start.in_set(AppSet::Input))
+            // Runs after everything else moving the camera, so captured frames are at exactly the snapped angle
+            .add_systems(
+                Update,
+                update
+                    .after(update_input)
+                    .after(attract::orbit)
+                    .in_set(AppSet::CameraFollow),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct TurntableSettings {
+    pub key: KeyCode,
+    /// Seconds for a full turn
+    pub duration: f32,
+    /// Number of screenshots taken during a full turn
+    pub frames: u32,
+    pub output_dir: PathBuf,
+}
+
+impl Default for TurntableSettings {
+    fn default() -> Self {
+        Self {
+            key: KeyCode::KeyT,
+            duration: 12.0,
+            frames: 36,
+            output_dir: PathBuf::from("screenshots/turntable"),
+        }
+    }
+}
+
+/// Present while a turntable sequence is running
+#[derive(Resource)]
+struct Turntable {
+    start_rotation: Quat,
+    elapsed: f32,
+    next_frame: u32,
+}
+
+fn start(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    settings: Res<TurntableSettings>,
+    turntable: Option<Res<Turntable>>,
+    cameras: Query<&Transform, With<PanOrbitCamera>>,
+) {
+    if turntable.is_some() || !input.just_pressed(settings.key) || settings.frames == 0 {
+        return;
+    }
+    let Ok(transform) = cameras.get_single() else {
+        return;
+    };
+    if let Err(err) = std::fs::create_dir_all(&settings.output_dir) {
+        log::error!("Can't create turntable folder {}: {err}", settings.output_dir.display());
+        return;
+    }
+
+    log::info!(
+        "Capturing {} turntable frames into {}",
+        settings.frames,
+        settings.output_dir.display()
+    );
+    commands.insert_resource(Turntable {
+        start_rotation: transform.rotation,
+        elapsed: 0.0,
+        next_frame: 0,
+    });
+}
+
+fn update(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<TurntableSettings>,
+    turntable: Option<ResMut<Turntable>>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut cameras: Query<(&PanOrbitCamera, &mut Transform)>,
+) {
+    let Some(mut turntable) = turntable else {
+        return;
+    };
+    let Ok((camera, mut transform)) = cameras.get_single_mut() else {
+        commands.remove_resource::<Turntable>();
+        return;
+    };
+
+    if turntable.next_frame == settings.frames {
+        // Back to where the camera was before the turn
+        transform.rotation = turntable.start_rotation;
+        camera.place(&mut transform);
+        commands.remove_resource::<Turntable>();
+        log::info!("Turntable capture finished");
+        return;
+    }
+
+    turntable.elapsed += time.delta_seconds();
+    let frame_angle = turntable.next_frame as f32 / settings.frames as f32 * TAU;
+    let mut angle = turntable.elapsed / settings.duration * TAU;
+
+    // Snap to the exact frame angle when a capture is due, so frames line up regardless of frame rate
+    if angle >= frame_angle {
+        angle = frame_angle;
+        if let Ok(window) = windows.get_single() {
+            let path = settings
+                .output_dir
+                .join(format!("turntable_{:03}.png", turntable.next_frame));
+            if let Err(err) = screenshots.save_screenshot_to_disk(window, path) {
+                log::warn!("Skipped turntable frame {}: {err}", turntable.next_frame);
+            }
+        }
+        turntable.next_frame += 1;
+    }
+
+    transform.rotation = Quat::from_rotation_y(angle) * turntable.start_rotation;
+    camera.place(&mut transform);
+}
@@ -0,0 +1,29 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::ecs::event::{Event, EventWriter};
+use bevy::ecs::system::Res;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::ButtonInput;
+use bevy::prelude::IntoSystemConfigs;
+
+use crate::schedule::AppSet;
+
+/// Aircraft commands. The keyboard and anything else that drives the aircraft send these events instead of touching
+/// the aircraft components directly; the subsystems owning the state consume them in [`AppSet::Animation`] and later.
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToggleGear>()
+            .add_systems(Update, keyboard_controls.in_set(AppSet::Input));
+    }
+}
+
+/// Raises the landing gear if it is down and lowers it if it is up
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct ToggleGear;
+
+pub fn keyboard_controls(input: Res<ButtonInput<KeyCode>>, mut toggle_gear: EventWriter<ToggleGear>) {
+    if input.just_pressed(KeyCode::KeyG) {
+        toggle_gear.send(ToggleGear);
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::color::Color;
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::system::{Commands, Query, Res, Resource};
+use bevy::math::{Quat, Vec3};
+use bevy::pbr::{AmbientLight, DirectionalLight, DirectionalLightBundle, DirectionalLightShadowMap};
+use bevy::prelude::{default, IntoSystemConfigs};
+use bevy::reflect::Reflect;
+use bevy::render::camera::ClearColor;
+use bevy::time::Time;
+use bevy::transform::components::Transform;
+
+use crate::schedule::AppSet;
+
+/// Sky color, ambient light and the sun
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AmbientLight {
+            color: Color::WHITE,
+            brightness: 1.0 / 5.0f32,
+        })
+        .insert_resource(DirectionalLightShadowMap { size: 4096 })
+        .insert_resource(ClearColor(Color::srgb(0.7, 0.92, 0.96)))
+        .init_resource::<SunSettings>()
+        .add_systems(Startup, spawn_sun)
+        .add_systems(Update, animate_sun.in_set(AppSet::Animation));
+    }
+}
+
+#[derive(Resource, Reflect)]
+pub struct SunSettings {
+    /// Angular speed of the sun around `axis` in radians per second, e.g. `PI / 5.0` for a full turn every ten
+    /// seconds. The sun stays where it was spawned when this is zero.
+    pub rotation_speed: f32,
+    /// World axis the sun rotates around
+    pub axis: Vec3,
+}
+
+impl Default for SunSettings {
+    fn default() -> Self {
+        Self {
+            rotation_speed: 0.0,
+            axis: Vec3::Y,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Sun {
+    /// Rotation the sun was spawned with, the animation is applied on top of it
+    base_rotation: Quat,
+}
+
+pub fn spawn_sun(mut commands: Commands) {
+    let transform = Transform::from_translation(Vec3::new(2.0, 0.5, 5.0)).looking_at(Vec3::ZERO, Vec3::Y);
+
+    commands.spawn((
+        Sun {
+            base_rotation: transform.rotation,
+        },
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            transform,
+            ..default()
+        },
+    ));
+}
+
+pub fn animate_sun(time: Res<Time>, settings: Res<SunSettings>, mut query: Query<(&Sun, &mut Transform)>) {
+    if settings.rotation_speed == 0.0 && !settings.is_changed() {
+        return;
+    }
+    let Some(axis) = settings.axis.try_normalize() else {
+        return;
+    };
+
+    let rotation = Quat::from_axis_angle(axis, time.elapsed_seconds() * settings.rotation_speed);
+    for (sun, mut transform) in &mut query {
+        transform.rotation = rotation * sun.base_rotation;
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::{Asset, AssetLoadFailedEvent};
+use bevy::color::Color;
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::component::Component;
+use bevy::ecs::event::{Event, EventReader, EventWriter};
+use bevy::ecs::query::With;
+use bevy::ecs::system::{Commands, Query, Res, ResMut, Resource};
+use bevy::gltf::Gltf;
+use bevy::input::keyboard::KeyCode;
+use bevy::input::ButtonInput;
+use bevy::log;
+use bevy::prelude::{default, IntoSystemConfigs};
+use bevy::render::texture::Image;
+use bevy::render::view::Visibility;
+use bevy::scene::Scene;
+use bevy::text::{Text, TextStyle};
+use bevy::ui::node_bundles::TextBundle;
+use bevy::ui::{PositionType, Style, UiRect, Val};
+
+use crate::animation::AnimationKind;
+use crate::schedule::AppSet;
+
+/// Collects [`AppError`]s, logs them and lists them in an on-screen panel until dismissed with Enter
+pub struct ErrorPlugin;
+
+impl Plugin for ErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AppError>()
+            .init_resource::<ErrorLog>()
+            .add_systems(Startup, spawn_panel)
+            .add_systems(
+                Update,
+                (
+                    (
+                        report_failed_loads::<Gltf>,
+                        report_failed_loads::<Scene>,
+                        report_failed_loads::<Image>,
+                    ),
+                    collect,
+                    dismiss,
+                    update_panel,
+                )
+                    .chain()
+                    .in_set(AppSet::Ui),
+            );
+    }
+}
+
+/// Problems the app recovers from instead of panicking. Send them as events to have them logged and shown.
+#[derive(Event, Clone, Debug)]
+pub enum AppError {
+    /// A command line flag that is unknown, lacks its value or has one that could not be used. The default is used
+    /// instead, `value` is empty if there was none.
+    InvalidArgument {
+        flag: String,
+        value: String,
+        reason: String,
+    },
+    /// Assets missing from the asset folder, placeholders are used instead
+    MissingAssets { root: PathBuf, paths: Vec<String> },
+    /// An asset that failed to load
+    AssetLoad { path: String, reason: String },
+    /// A dropped file that is not a supported model
+    UnsupportedModel(PathBuf),
+    /// A loaded model with no clip matching one of the animations the app drives
+    UnmatchedAnimation { model: String, kind: AnimationKind },
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::InvalidArgument { flag, value, reason } if value.is_empty() => {
+                write!(f, "Invalid argument {flag}: {reason}")
+            },
+            AppError::InvalidArgument { flag, value, reason } => {
+                write!(f, "Invalid value `{value}` for {flag} ({reason}), using the default")
+            },
+            AppError::MissingAssets { root, paths } => {
+                write!(f, "Missing from {}, using placeholders:", root.display())?;
+                for path in paths {
+                    write!(f, "\n  - {path}")?;
+                }
+                Ok(())
+            },
+            AppError::AssetLoad { path, reason } => write!(f, "Failed to load {path}: {reason}"),
+            AppError::UnsupportedModel(path) => {
+                write!(
+                    f,
+                    "Can't load {}, only .gltf and .glb models are supported",
+                    path.display()
+                )
+            },
+            AppError::UnmatchedAnimation { model, kind } => {
+                write!(f, "{model} has no clip for {kind:?}, it will not be animated")
+            },
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Errors reported so far that have not been dismissed
+#[derive(Resource, Default)]
+pub struct ErrorLog {
+    pub errors: Vec<AppError>,
+}
+
+#[derive(Component)]
+struct ErrorPanel;
+
+fn spawn_panel(mut commands: Commands) {
+    commands.spawn((
+        ErrorPanel,
+        TextBundle::from_section("", TextStyle {
+            font_size: 18.0,
+            color: Color::srgb(1.0, 0.85, 0.85),
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        })
+        .with_background_color(Color::srgba(0.35, 0.0, 0.0, 0.8)),
+    ));
+}
+
+/// Reports failed loads of `A` assets. Besides the glTF files themselves this covers the scenes and external textures
+/// they reference, which fail on their own without failing the glTF load.
+fn report_failed_loads<A: Asset>(mut failed: EventReader<AssetLoadFailedEvent<A>>, mut errors: EventWriter<AppError>) {
+    for event in failed.read() {
+        errors.send(AppError::AssetLoad {
+            path: event.path.to_string(),
+            reason: event.error.to_string(),
+        });
+    }
+}
+
+fn collect(mut events: EventReader<AppError>, mut error_log: ResMut<ErrorLog>) {
+    for error in events.read() {
+        log::error!("{error}");
+        error_log.errors.push(error.clone());
+    }
+}
+
+fn dismiss(input: Res<ButtonInput<KeyCode>>, mut error_log: ResMut<ErrorLog>) {
+    if input.just_pressed(KeyCode::Enter) && !error_log.errors.is_empty() {
+        error_log.errors.clear();
+    }
+}
+
+fn update_panel(error_log: Res<ErrorLog>, mut panels: Query<(&mut Text, &mut Visibility), With<ErrorPanel>>) {
+    if !error_log.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in &mut panels {
+        if error_log.errors.is_empty() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        let mut lines: Vec<_> = error_log.errors.iter().map(ToString::to_string).collect();
+        lines.push("Press Enter to dismiss".to_string());
+        text.sections[0].value = lines.join("\n");
+        *visibility = Visibility::Inherited;
+    }
+}
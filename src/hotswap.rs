@@ -0,0 +1,149 @@
+use std::ffi::OsStr;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::asset::{AssetPath, AssetServer, Assets, Handle, LoadState};
+use bevy::ecs::entity::Entity;
+use bevy::ecs::event::{EventReader, EventWriter};
+use bevy::ecs::query::With;
+use bevy::ecs::system::{Commands, Query, Res, ResMut};
+use bevy::gltf::GltfAssetLabel;
+use bevy::log;
+use bevy::pbr::StandardMaterial;
+use bevy::prelude::{AnimationGraph, IntoSystemConfigs};
+use bevy::render::mesh::Mesh;
+use bevy::scene::Scene;
+use bevy::window::FileDragAndDrop;
+
+use crate::animation::Animations;
+use crate::error::AppError;
+use crate::schedule::AppSet;
+use crate::PlaneMovement;
+
+/// Replaces the aircraft with a glTF model dropped onto the window, for quickly checking new models
+pub struct HotSwapPlugin;
+
+impl Plugin for HotSwapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, swap_dropped_model.in_set(AppSet::Input));
+    }
+}
+
+pub fn swap_dropped_model(
+    mut commands: Commands,
+    mut drops: EventReader<FileDragAndDrop>,
+    asset_server: Res<AssetServer>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    aircraft: Query<Entity, With<PlaneMovement>>,
+    mut errors: EventWriter<AppError>,
+) {
+    for drop in drops.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = drop else {
+            continue;
+        };
+        let is_gltf = path_buf
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gltf") || ext.eq_ignore_ascii_case("glb"));
+        if !is_gltf {
+            errors.send(AppError::UnsupportedModel(path_buf.clone()));
+            continue;
+        }
+
+        log::info!("Replacing the aircraft with {}", path_buf.display());
+        let path = AssetPath::from(path_buf.clone());
+        let gltf = asset_server.load(path.clone());
+        // Loading a path again hands back the existing handle, so a file dropped before has to be reloaded to pick up
+        // changes made to it since
+        if matches!(
+            asset_server.get_load_state(&gltf),
+            Some(LoadState::Loaded | LoadState::Failed(_))
+        ) {
+            asset_server.reload(path.clone());
+        }
+        commands.insert_resource(Animations::new(gltf, &mut graphs));
+
+        // Changing the scene handle makes Bevy despawn the old scene instance and spawn the new one in its place
+        let scene: Handle<Scene> = asset_server.load(GltfAssetLabel::Scene(0).from_asset(path));
+        for entity in &aircraft {
+            commands
+                .entity(entity)
+                .remove::<(Handle<Mesh>, Handle<StandardMaterial>)>()
+                .insert(scene.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+    use std::{fs, thread};
+
+    use bevy::app::App;
+    use bevy::ecs::entity::Entity;
+    use bevy::window::FileDragAndDrop;
+
+    use crate::animation::{AnimationKind, Animations};
+    use crate::test_support::{headless_app, options};
+
+    /// A node with a translation clip named "Gear", with the buffer embedded so the file stands on its own
+    const GEAR_MODEL: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "name": "Wheel" }],
+        "buffers": [{
+            "byteLength": 32,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAgD8AAAAAAAAAAAAAAAAAAAAAAACAPwAAAAA="
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 8 },
+            { "buffer": 0, "byteOffset": 8, "byteLength": 24 }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR", "min": [0.0], "max": [1.0] },
+            { "bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC3" }
+        ],
+        "animations": [{
+            "name": "Gear",
+            "channels": [{ "sampler": 0, "target": { "node": 0, "path": "translation" } }],
+            "samplers": [{ "input": 0, "output": 1 }]
+        }]
+    }"#;
+
+    /// Drops `path` onto the window and updates the app until the model's animations are known
+    fn drop_and_load(app: &mut App, path: &Path) -> bool {
+        app.world_mut().send_event(FileDragAndDrop::DroppedFile {
+            window: Entity::PLACEHOLDER,
+            path_buf: path.to_path_buf(),
+        });
+        for _ in 0..500 {
+            app.update();
+            let animations = app.world().get_resource::<Animations>();
+            if animations.is_some_and(|animations| animations.get(AnimationKind::LandingGear).is_some()) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn dropping_the_same_model_twice_keeps_the_animations() {
+        let dir = std::env::temp_dir().join(format!("checkmate-hotswap-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gear.gltf");
+        fs::write(&path, GEAR_MODEL).unwrap();
+
+        let mut app = headless_app(options(&[]));
+        let first = drop_and_load(&mut app, &path);
+        let second = drop_and_load(&mut app, &path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(first, "the gear clip was not found after the first drop");
+        assert!(
+            second,
+            "the gear clip was not found after dropping the same model again"
+        );
+    }
+}
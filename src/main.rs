@@ -1,37 +1,60 @@
-use std::f32::consts::{FRAC_PI_4, PI};
+use std::ops::Range;
 
-use bevy::animation::{animate_targets, AnimationClip, AnimationPlayer};
+use animation::{AircraftAnimationPlugin, Animations};
+use args::Options;
+use assets::{asset_root, check_assets};
 use bevy::app::{App, Startup, Update};
-use bevy::asset::{AssetServer, Assets, Handle};
+use bevy::asset::{AssetServer, Assets};
 use bevy::color::{Color, ColorToComponents, LinearRgba};
 use bevy::ecs::component::Component;
-use bevy::ecs::query::{Added, With};
-use bevy::ecs::system::{Commands, Local, Query, Res, ResMut, Resource};
+use bevy::ecs::event::EventWriter;
+use bevy::ecs::system::{Commands, Query, Res, ResMut, Resource};
 use bevy::gltf::GltfAssetLabel;
 use bevy::input::keyboard::KeyCode;
 use bevy::input::ButtonInput;
-use bevy::math::primitives::Plane3d;
-use bevy::math::{EulerRot, Quat, Vec3};
-use bevy::pbr::{
-    AmbientLight, DirectionalLight, DirectionalLightBundle, DirectionalLightShadowMap, PbrBundle, StandardMaterial,
-};
-use bevy::prelude::{default, AnimationGraph, AnimationNodeIndex, Entity, IntoSystemConfigs, MeshBuilder};
+use bevy::math::primitives::{Cuboid, Plane3d};
+use bevy::math::Vec3;
+use bevy::pbr::{PbrBundle, StandardMaterial};
+use bevy::prelude::{default, AnimationGraph, Entity, IntoSystemConfigs, MeshBuilder};
 use bevy::reflect::Reflect;
-use bevy::render::camera::ClearColor;
 use bevy::render::mesh::{Mesh, Meshable};
 use bevy::scene::SceneBundle;
 use bevy::transform::components::Transform;
+use bevy::ui::UiScale;
 use bevy::window::Window;
-use bevy::{log, DefaultPlugins};
-use camera::panorbit::PanOrbitCameraPlugin;
+use bevy::DefaultPlugins;
+use camera::CameraPlugin;
+use controls::ControlsPlugin;
 use diagnostics::DiagnosticsPlugin;
+use environment::EnvironmentPlugin;
+use error::{AppError, ErrorPlugin};
+use hotswap::HotSwapPlugin;
+use schedule::{AppSet, SchedulePlugin};
 use utils::combine_meshes;
 
+mod animation;
+mod args;
+mod assets;
 mod camera;
+mod controls;
 mod diagnostics;
-// mod old;
+mod environment;
+mod error;
+mod hotswap;
+mod schedule;
 mod utils;
 
+#[cfg(test)]
+mod test_support;
+
+pub const AIRCRAFT_MODEL: &str = "su-75_anim/su-75.gltf";
+
+/// Cell columns (x) and rows (z) of the chessboard strip under the aircraft
+const CHESSBOARD_COLUMNS: Range<i32> = -7..8;
+const CHESSBOARD_ROWS: Range<i32> = -7..250;
+/// Number of rows merged into a single chessboard mesh
+const CHESSBOARD_CHUNK_ROWS: usize = 16;
+
 pub const LANDSCAPE_SIZE: f32 = 1200.0;
 pub const LANDSCAPE_SIZE_HALF: f32 = LANDSCAPE_SIZE * 0.5;
 
@@ -44,36 +67,77 @@ pub struct PlaneSettings {
     speed: f32,
 }
 
+/// Asset path of the model the aircraft is spawned with
+#[derive(Resource)]
+struct AircraftModel(String);
+
 #[derive(Component)]
 pub struct PlaneMovement {
     target_pos: Vec3,
     timer: f32,
 }
 
-#[derive(Resource)]
-struct Animations {
-    animations: Vec<AnimationNodeIndex>,
-    graph: Handle<AnimationGraph>,
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = Options::parse(&args);
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
+    // The perf overlay draws through the renderer, so it goes with the window and render plugins
+    if !options.kiosk {
+        app.add_plugins(DiagnosticsPlugin);
+    }
+    build_app(&mut app, options);
+    app.run();
 }
 
-fn main() {
-    App::new()
-        .insert_resource(AmbientLight {
-            color: Color::WHITE,
-            brightness: 1.0 / 5.0f32,
+/// Adds everything the app consists of on top of the window and render plugins, which `app` must already have
+fn build_app(app: &mut App, options: Options) {
+    app.insert_resource(UiScale(options.ui_scale))
+        .add_plugins(SchedulePlugin)
+        .add_plugins(ErrorPlugin)
+        .add_plugins(ControlsPlugin)
+        .add_plugins(EnvironmentPlugin)
+        .insert_resource(options.sun)
+        .add_plugins(CameraPlugin {
+            settings: options.camera,
+            turntable: !options.kiosk,
         })
-        .insert_resource(DirectionalLightShadowMap { size: 4096 })
-        .add_plugins(DefaultPlugins)
-        .add_plugins(DiagnosticsPlugin)
-        .add_plugins(PanOrbitCameraPlugin)
-        .add_systems(Startup, (chessboard_land_spawn, setup))
-        .add_systems(Update, attach_animations.before(animate_targets))
-        .add_systems(Update, control_land_gear_animation)
-        .add_systems(Update, close_on_esc)
-        .run();
+        .insert_resource(options.attract)
+        .insert_resource(options.turntable)
+        .add_plugins(AircraftAnimationPlugin)
+        .insert_resource(options.animation_rules)
+        .insert_resource(AircraftModel(options.aircraft_model))
+        .add_systems(Startup, (chessboard_land_spawn, setup));
+
+    if !options.kiosk {
+        app.add_plugins(HotSwapPlugin)
+            .add_systems(Update, close_on_esc.in_set(AppSet::Input));
+    }
+
+    for err in options.errors {
+        app.world_mut().send_event(err);
+    }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut graphs: ResMut<Assets<AnimationGraph>>) {
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut errors: EventWriter<AppError>,
+    model: Res<AircraftModel>,
+) {
+    let model = model.0.as_str();
+    let missing_assets = check_assets(&[model]);
+    if !missing_assets.is_empty() {
+        errors.send(AppError::MissingAssets {
+            root: asset_root(),
+            paths: missing_assets.iter().map(ToString::to_string).collect(),
+        });
+    }
+
     commands.insert_resource(PlaneSettings {
         move_interval: 1.3,
         box_area: 6.0,
@@ -81,46 +145,25 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut graphs: Res
         wobble_speed: 5.0,
         rotation_speed: 0.7,
     });
-    commands.insert_resource(ClearColor(Color::srgb(0.7, 0.92, 0.96)));
 
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            shadows_enabled: true,
+    let movement = PlaneMovement {
+        target_pos: Vec3::ZERO,
+        timer: 0.0,
+    };
+    if missing_assets.contains(&model) {
+        // Magenta box in place of the aircraft, so a missing model is obvious instead of an empty scene
+        commands.spawn((movement, PbrBundle {
+            mesh: meshes.add(Cuboid::new(2.0, 0.5, 6.0)),
+            material: materials.add(Color::srgb(1.0, 0.0, 1.0)),
             ..default()
-        },
-        transform: Transform::from_translation(Vec3::new(2.0, 0.5, 5.0)).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
-
-    // Build the animation graph
-    let mut graph = AnimationGraph::new();
-    let animations = graph
-        .add_clips(
-            [GltfAssetLabel::Animation(0).from_asset("su-75_anim/su-75.gltf")]
-                .into_iter()
-                .map(|path| asset_server.load(path)),
-            1.0,
-            graph.root,
-        )
-        .collect();
-
-    // Insert a resource with the current scene information
-    let graph = graphs.add(graph);
-    commands.insert_resource(Animations {
-        animations,
-        graph: graph.clone(),
-    });
-
-    commands.spawn((
-        PlaneMovement {
-            target_pos: Vec3::ZERO,
-            timer: 0.0,
-        },
-        SceneBundle {
-            scene: asset_server.load("su-75_anim/su-75.gltf#Scene0"),
+        }));
+    } else {
+        commands.insert_resource(Animations::new(asset_server.load(model.to_string()), &mut graphs));
+        commands.spawn((movement, SceneBundle {
+            scene: asset_server.load(GltfAssetLabel::Scene(0).from_asset(model.to_string())),
             ..default()
-        },
-    ));
+        }));
+    }
 }
 
 fn chessboard_land_spawn(
@@ -128,83 +171,40 @@ fn chessboard_land_spawn(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let mut mesh_data = Vec::new();
     let cell_mesh = Plane3d::default().mesh().size(2.0, 2.0).build();
+    let material = materials.add(Color::WHITE);
 
-    for x in -7..8 {
-        for z in -7..250 {
-            let transform = Transform::from_xyz(x as f32 * 2.0, -2.31, z as f32 * 2.0);
-
-            let mut mesh = cell_mesh.clone();
-            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![
-                if (x + z) % 2 == 0 {
-                    Color::LinearRgba(LinearRgba::RED)
-                } else {
-                    Color::WHITE
-                }
-                .to_linear()
-                .to_f32_array();
-                mesh.count_vertices()
-            ]);
-            mesh_data.push((mesh, transform));
-        }
-    }
-
-    let mesh = meshes.add(combine_meshes(&mesh_data, true, false, false, true));
-    commands.spawn(PbrBundle {
-        mesh,
-        material: materials.add(Color::WHITE),
-        ..default()
-    });
-}
-
-/// Attaches the animation graph to the scene
-fn attach_animations(
-    mut commands: Commands,
-    to_animated_entities: Query<(Entity, &AnimationPlayer), Added<AnimationPlayer>>,
-    animations: Res<Animations>,
-) {
-    for (entity, _player) in &to_animated_entities {
-        log::info!("Attaching animations");
-        commands.entity(entity).insert(animations.graph.clone());
-    }
-}
+    // Merge the cells chunk by chunk rather than into a single mesh, so each chunk gets its own bounding box and the
+    // parts of the strip behind the camera can be culled
+    for chunk_start in (CHESSBOARD_ROWS.start..CHESSBOARD_ROWS.end).step_by(CHESSBOARD_CHUNK_ROWS) {
+        let chunk_end = (chunk_start + CHESSBOARD_CHUNK_ROWS as i32).min(CHESSBOARD_ROWS.end);
+        let mut mesh_data = Vec::new();
 
-fn control_land_gear_animation(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut animation_players: Query<&mut AnimationPlayer>,
-    animations: Res<Animations>,
-    animation_clips: Res<Assets<AnimationClip>>,
-    animation_graphs: Res<Assets<AnimationGraph>>,
-    mut reverse: Local<bool>,
-) {
-    if keyboard_input.just_pressed(KeyCode::KeyG) {
-        let Some(animation_graph) = animation_graphs.get(&animations.graph) else {
-            return;
-        };
+        for x in CHESSBOARD_COLUMNS {
+            for z in chunk_start..chunk_end {
+                let transform = Transform::from_xyz(x as f32 * 2.0, -2.31, z as f32 * 2.0);
 
-        for (node_index, mut player) in [animations.animations[0]].into_iter().zip(&mut animation_players) {
-            let animation_node = &animation_graph[node_index];
-            let animation_start_time = if *reverse {
-                animation_node
-                    .clip
-                    .as_ref()
-                    .and_then(|clip_handle| animation_clips.get(clip_handle).map(|clip| clip.duration()))
-                    .unwrap_or_default()
-            } else {
-                0.0
-            };
-
-            if player.all_finished() {
-                for (_, playing_animation) in player.playing_animations_mut() {
-                    playing_animation.replay();
-                }
-                player.seek_all_by(animation_start_time);
+                let mut mesh = cell_mesh.clone();
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![
+                    if (x + z) % 2 == 0 {
+                        Color::LinearRgba(LinearRgba::RED)
+                    } else {
+                        Color::WHITE
+                    }
+                    .to_linear()
+                    .to_f32_array();
+                    mesh.count_vertices()
+                ]);
+                mesh_data.push((mesh, transform));
             }
-            player.adjust_speeds(-1.0);
-            player.play(node_index);
         }
-        *reverse = !*reverse;
+
+        let mesh = meshes.add(combine_meshes(&mesh_data, true, false, false, true));
+        commands.spawn(PbrBundle {
+            mesh,
+            material: material.clone(),
+            ..default()
+        });
     }
 }
 
@@ -223,3 +223,81 @@ pub fn close_on_esc(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::App;
+    use bevy::ecs::component::Component;
+    use bevy::ecs::query::With;
+    use bevy::render::camera::Projection;
+
+    use crate::camera::attract::AttractSettings;
+    use crate::camera::panorbit::PanOrbitCamera;
+    use crate::camera::turntable::TurntablePlugin;
+    use crate::error::{AppError, ErrorLog};
+    use crate::hotswap::HotSwapPlugin;
+    use crate::test_support::{headless_app, options, MISSING_MODEL};
+    use crate::PlaneMovement;
+
+    fn run(app: &mut App) {
+        for _ in 0..10 {
+            app.update();
+        }
+    }
+
+    fn count<C: Component>(app: &mut App) -> usize {
+        let world = app.world_mut();
+        world.query_filtered::<(), With<C>>().iter(world).count()
+    }
+
+    #[test]
+    fn boots_and_spawns_camera_and_aircraft() {
+        let mut app = headless_app(options(&[]));
+        run(&mut app);
+
+        assert_eq!(count::<PanOrbitCamera>(&mut app), 1);
+        assert_eq!(count::<PlaneMovement>(&mut app), 1);
+        assert!(app.is_plugin_added::<HotSwapPlugin>());
+        assert!(app.is_plugin_added::<TurntablePlugin>());
+    }
+
+    #[test]
+    fn kiosk_leaves_out_hot_swap_and_turntable() {
+        let mut app = headless_app(options(&["--kiosk"]));
+        run(&mut app);
+
+        assert_eq!(count::<PanOrbitCamera>(&mut app), 1);
+        assert!(!app.is_plugin_added::<HotSwapPlugin>());
+        assert!(!app.is_plugin_added::<TurntablePlugin>());
+    }
+
+    #[test]
+    fn command_line_settings_are_applied() {
+        let mut app = headless_app(options(&["--attract-timeout", "5", "--fov", "60"]));
+        run(&mut app);
+
+        assert_eq!(app.world().resource::<AttractSettings>().idle_timeout, 5.0);
+        let world = app.world_mut();
+        let projection = world
+            .query_filtered::<&Projection, With<PanOrbitCamera>>()
+            .single(world);
+        let Projection::Perspective(projection) = projection else {
+            panic!("expected a perspective projection");
+        };
+        assert_eq!(projection.fov, 60.0_f32.to_radians());
+    }
+
+    #[test]
+    fn argument_and_asset_errors_reach_the_log() {
+        let mut app = headless_app(options(&["--bogus"]));
+        run(&mut app);
+
+        let errors = &app.world().resource::<ErrorLog>().errors;
+        assert!(errors
+            .iter()
+            .any(|err| matches!(err, AppError::InvalidArgument { flag, .. } if flag == "--bogus")));
+        assert!(errors
+            .iter()
+            .any(|err| matches!(err, AppError::MissingAssets { paths, .. } if paths == &[MISSING_MODEL])));
+    }
+}
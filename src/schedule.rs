@@ -0,0 +1,37 @@
+use bevy::app::{App, Plugin, Update};
+use bevy::ecs::schedule::{IntoSystemSetConfigs, SystemSet};
+
+/// Ordered stages of the [`Update`] schedule. Plugins put their systems into these sets, extensions can use them to
+/// run systems at a well-defined point, e.g. `my_system.after(AppSet::Animation).before(AppSet::CameraFollow)`.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AppSet {
+    /// Reads keyboard and mouse input and turns it into commands
+    Input,
+    /// Moves simulated objects
+    Physics,
+    /// Updates animation graphs, players and other animated scene elements
+    Animation,
+    /// Moves cameras after everything they look at has moved
+    CameraFollow,
+    /// Updates on-screen overlays
+    Ui,
+}
+
+/// Chains the [`AppSet`]s in declaration order
+pub struct SchedulePlugin;
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (
+                AppSet::Input,
+                AppSet::Physics,
+                AppSet::Animation,
+                AppSet::CameraFollow,
+                AppSet::Ui,
+            )
+                .chain(),
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::animation::AnimationClip;
+use bevy::app::App;
+use bevy::asset::{AssetApp, AssetPlugin};
+use bevy::gltf::GltfPlugin;
+use bevy::input::InputPlugin;
+use bevy::pbr::StandardMaterial;
+use bevy::prelude::AnimationGraph;
+use bevy::render::mesh::Mesh;
+use bevy::render::texture::Image;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::scene::Scene;
+use bevy::window::FileDragAndDrop;
+use bevy::MinimalPlugins;
+
+use crate::args::Options;
+
+/// Model path that is never in the asset folder, so tests take the placeholder path whether or not the real assets are
+/// checked out
+pub const MISSING_MODEL: &str = "missing/aircraft.gltf";
+
+/// Parses `args` like the command line, with the aircraft model pinned to [`MISSING_MODEL`]
+pub fn options(args: &[&str]) -> Options {
+    let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+    let mut options = Options::parse(&args);
+    options.aircraft_model = MISSING_MODEL.to_string();
+    options
+}
+
+/// The app as [`crate::build_app`] assembles it, with the window and render plugins, which a test can't open, replaced
+/// by the assets and resources the app's own plugins rely on
+pub fn headless_app(options: Options) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), InputPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>()
+        .init_asset::<Scene>()
+        .init_asset::<AnimationClip>()
+        .init_asset::<AnimationGraph>()
+        .add_plugins(GltfPlugin::default())
+        .init_resource::<ScreenshotManager>()
+        .add_event::<FileDragAndDrop>();
+    crate::build_app(&mut app, options);
+    app.finish();
+    app.cleanup();
+    app
+}
@@ -1,8 +1,15 @@
-use bevy::math::{Mat3, Vec3, Vec4Swizzles};
+use bevy::log;
+use bevy::math::{Mat3, Vec3, Vec4, Vec4Swizzles};
 use bevy::render::mesh::{Indices, Mesh, PrimitiveTopology, VertexAttributeValues};
 use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::VertexFormat;
+use bevy::tasks::{ComputeTaskPool, TaskPool};
 use bevy::transform::components::Transform;
 
+/// Merges `meshes` into a single mesh, baking each transform into the vertex data.
+///
+/// The output buffers are sized up front and every source mesh writes into its own disjoint range of them, so the
+/// meshes are processed in parallel on the [`ComputeTaskPool`] without any per-vertex allocation.
 pub fn combine_meshes<'a>(
     meshes: impl IntoIterator<Item = &'a (Mesh, Transform)>,
     use_normals: bool,
@@ -10,112 +17,364 @@ pub fn combine_meshes<'a>(
     use_uvs: bool,
     use_colors: bool,
 ) -> Mesh {
-    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    let mut meshes: Vec<_> = meshes.into_iter().collect();
+    let source_count = meshes.len();
+    meshes.retain(|(mesh, _)| {
+        mesh.primitive_topology() == PrimitiveTopology::TriangleList
+            && matches!(
+                mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+                Some(VertexAttributeValues::Float32x3(_))
+            )
+    });
+    if meshes.len() < source_count {
+        log::warn!(
+            "Skipped {} of {source_count} meshes while combining, only triangle lists with Float32x3 positions are \
+             supported",
+            source_count - meshes.len()
+        );
+    }
+    for (enabled, attribute) in [
+        (use_normals, Mesh::ATTRIBUTE_NORMAL),
+        (use_tangents, Mesh::ATTRIBUTE_TANGENT),
+        (use_uvs, Mesh::ATTRIBUTE_UV_0),
+        (use_colors, Mesh::ATTRIBUTE_COLOR),
+    ] {
+        if !enabled {
+            continue;
+        }
+        let lacking = meshes
+            .iter()
+            .filter(|(mesh, _)| mesh.attribute(attribute.id).map(VertexFormat::from) != Some(attribute.format))
+            .count();
+        if lacking > 0 {
+            log::warn!(
+                "{lacking} of {} meshes have no {:?} {} attribute, it is left zeroed for them in the combined mesh",
+                meshes.len(),
+                attribute.format,
+                attribute.name
+            );
+        }
+    }
+
+    // Non-indexed meshes get one index per vertex
+    let index_len = |mesh: &Mesh| mesh.indices().map_or(vertex_positions(mesh).len(), Indices::len);
+    let vertex_count: usize = meshes.iter().map(|(mesh, _)| vertex_positions(mesh).len()).sum();
+    let index_count: usize = meshes.iter().map(|(mesh, _)| index_len(mesh)).sum();
+    let optional_count = |enabled: bool| if enabled { vertex_count } else { 0 };
 
-    let mut positions: Vec<[f32; 3]> = Vec::new();
-    let mut normals: Vec<[f32; 3]> = Vec::new();
-    let mut tangets: Vec<[f32; 4]> = Vec::new();
-    let mut uvs: Vec<[f32; 2]> = Vec::new();
-    let mut colors: Vec<[f32; 4]> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
+    let mut positions: Vec<[f32; 3]> = vec![[0.0; 3]; vertex_count];
+    let mut normals: Vec<[f32; 3]> = vec![[0.0; 3]; optional_count(use_normals)];
+    let mut tangents: Vec<[f32; 4]> = vec![[0.0; 4]; optional_count(use_tangents)];
+    let mut uvs: Vec<[f32; 2]> = vec![[0.0; 2]; optional_count(use_uvs)];
+    let mut colors: Vec<[f32; 4]> = vec![[0.0; 4]; optional_count(use_colors)];
+    let mut indices: Vec<u32> = vec![0; index_count];
 
+    // Hand every source mesh the part of the output buffers it is going to fill
+    let mut slices = Vec::with_capacity(meshes.len());
+    let (mut positions_rest, mut normals_rest, mut tangents_rest, mut uvs_rest, mut colors_rest, mut indices_rest) = (
+        positions.as_mut_slice(),
+        normals.as_mut_slice(),
+        tangents.as_mut_slice(),
+        uvs.as_mut_slice(),
+        colors.as_mut_slice(),
+        indices.as_mut_slice(),
+    );
     let mut indices_offset = 0;
 
     for (mesh, transform) in meshes {
-        if let Some(Indices::U32(mesh_indices)) = mesh.indices() {
-            let matrix = transform.compute_matrix();
+        let positions_len = vertex_positions(mesh).len();
+        let optional_len = |enabled: bool| if enabled { positions_len } else { 0 };
 
-            let positions_len = if let Some(VertexAttributeValues::Float32x3(vert_positions)) =
-                mesh.attribute(Mesh::ATTRIBUTE_POSITION)
-            {
-                for pos in vert_positions {
-                    positions.push(matrix.transform_point3(Vec3::from(*pos)).into());
-                }
-                vert_positions.len()
-            } else {
-                0
+        slices.push(MeshSlices {
+            mesh,
+            transform,
+            indices_offset,
+            positions: split_off(&mut positions_rest, positions_len),
+            normals: split_off(&mut normals_rest, optional_len(use_normals)),
+            tangents: split_off(&mut tangents_rest, optional_len(use_tangents)),
+            uvs: split_off(&mut uvs_rest, optional_len(use_uvs)),
+            colors: split_off(&mut colors_rest, optional_len(use_colors)),
+            indices: split_off(&mut indices_rest, index_len(mesh)),
+        });
+        indices_offset += positions_len as u32;
+    }
+
+    let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+    let batch_size = slices.len().div_ceil(task_pool.thread_num()).max(1);
+    task_pool.scope(|scope| {
+        for batch in slices.chunks_mut(batch_size) {
+            scope.spawn(async move { batch.iter_mut().for_each(MeshSlices::write) });
+        }
+    });
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+    if use_normals {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    }
+
+    if use_tangents {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangents);
+    }
+
+    if use_uvs {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    }
+
+    if use_colors {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+
+    mesh.insert_indices(Indices::U32(indices));
+
+    mesh
+}
+
+/// A source mesh together with the ranges of the combined buffers it writes to. Buffers of disabled attributes are
+/// empty.
+struct MeshSlices<'a> {
+    mesh: &'a Mesh,
+    transform: &'a Transform,
+    indices_offset: u32,
+    positions: &'a mut [[f32; 3]],
+    normals: &'a mut [[f32; 3]],
+    tangents: &'a mut [[f32; 4]],
+    uvs: &'a mut [[f32; 2]],
+    colors: &'a mut [[f32; 4]],
+    indices: &'a mut [u32],
+}
+
+impl MeshSlices<'_> {
+    fn write(&mut self) {
+        let matrix = self.transform.compute_matrix();
+
+        for (out, pos) in self.positions.iter_mut().zip(vertex_positions(self.mesh)) {
+            *out = matrix.transform_point3(Vec3::from(*pos)).into();
+        }
+
+        if !self.normals.is_empty() {
+            // Comment below taken from mesh_normal_local_to_world() in mesh_functions.wgsl regarding
+            // transform normals from local to world coordinates:
+
+            // NOTE: The mikktspace method of normal mapping requires that the world normal is
+            // re-normalized in the vertex shader to match the way mikktspace bakes vertex tangents
+            // and normal maps so that the exact inverse process is applied when shading. Blender, Unity,
+            // Unreal Engine, Godot, and more all use the mikktspace method. Do not change this code
+            // unless you really know what you are doing.
+            // http://www.mikktspace.com/
+
+            let inverse_transpose_model = matrix.inverse().transpose();
+            let inverse_transpose_model = Mat3 {
+                x_axis: inverse_transpose_model.x_axis.xyz(),
+                y_axis: inverse_transpose_model.y_axis.xyz(),
+                z_axis: inverse_transpose_model.z_axis.xyz(),
             };
 
-            if use_uvs {
-                if let Some(VertexAttributeValues::Float32x2(vert_uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
-                    for uv in vert_uvs {
-                        uvs.push(*uv);
-                    }
+            if let Some(VertexAttributeValues::Float32x3(vert_normals)) = self.mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+                for (out, norm) in self.normals.iter_mut().zip(vert_normals) {
+                    *out = inverse_transpose_model
+                        .mul_vec3(Vec3::from(*norm))
+                        .normalize_or_zero()
+                        .into();
                 }
             }
+        }
 
-            if use_normals {
-                // Comment below taken from mesh_normal_local_to_world() in mesh_functions.wgsl regarding
-                // transform normals from local to world coordinates:
-
-                // NOTE: The mikktspace method of normal mapping requires that the world normal is
-                // re-normalized in the vertex shader to match the way mikktspace bakes vertex tangents
-                // and normal maps so that the exact inverse process is applied when shading. Blender, Unity,
-                // Unreal Engine, Godot, and more all use the mikktspace method. Do not change this code
-                // unless you really know what you are doing.
-                // http://www.mikktspace.com/
-
-                let inverse_transpose_model = matrix.inverse().transpose();
-                let inverse_transpose_model = Mat3 {
-                    x_axis: inverse_transpose_model.x_axis.xyz(),
-                    y_axis: inverse_transpose_model.y_axis.xyz(),
-                    z_axis: inverse_transpose_model.z_axis.xyz(),
-                };
-
-                if let Some(VertexAttributeValues::Float32x3(vert_normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
-                    for norm in vert_normals {
-                        normals.push(
-                            inverse_transpose_model
-                                .mul_vec3(Vec3::from(*norm))
-                                .normalize_or_zero()
-                                .into(),
-                        );
-                    }
-                }
-            }
+        if !self.tangents.is_empty() {
+            // Same as mesh_tangent_local_to_world() in mesh_functions.wgsl: tangents follow the model matrix itself,
+            // and the handedness in w flips when the transform mirrors the mesh
+            let model = Mat3::from_mat4(matrix);
+            let handedness = model.determinant().signum();
 
-            if use_tangents {
-                if let Some(VertexAttributeValues::Float32x4(vert_tangets)) = mesh.attribute(Mesh::ATTRIBUTE_TANGENT) {
-                    for tan in vert_tangets {
-                        tangets.push(*tan);
-                    }
+            if let Some(VertexAttributeValues::Float32x4(vert_tangents)) = self.mesh.attribute(Mesh::ATTRIBUTE_TANGENT)
+            {
+                for (out, tan) in self.tangents.iter_mut().zip(vert_tangents) {
+                    let tangent = model.mul_vec3(Vec4::from(*tan).xyz()).normalize_or_zero();
+                    *out = tangent.extend(tan[3] * handedness).into();
                 }
             }
+        }
 
-            if use_colors {
-                if let Some(VertexAttributeValues::Float32x4(vert_colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
-                    for color in vert_colors {
-                        colors.push(*color);
-                    }
-                }
+        if let Some(VertexAttributeValues::Float32x2(vert_uvs)) = self.mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            for (out, uv) in self.uvs.iter_mut().zip(vert_uvs) {
+                *out = *uv;
             }
+        }
 
-            for idx in mesh_indices {
-                indices.push(*idx + indices_offset);
+        if let Some(VertexAttributeValues::Float32x4(vert_colors)) = self.mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+            for (out, color) in self.colors.iter_mut().zip(vert_colors) {
+                *out = *color;
             }
-            indices_offset += positions_len as u32;
+        }
+
+        match self.mesh.indices() {
+            Some(mesh_indices) => {
+                for (out, idx) in self.indices.iter_mut().zip(mesh_indices.iter()) {
+                    *out = idx as u32 + self.indices_offset;
+                }
+            },
+            None => {
+                for (out, idx) in self.indices.iter_mut().zip(0..) {
+                    *out = idx + self.indices_offset;
+                }
+            },
         }
     }
+}
 
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+fn vertex_positions(mesh: &Mesh) -> &[[f32; 3]] {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => positions,
+        _ => &[],
+    }
+}
 
-    if use_normals {
-        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+/// Splits the first `len` elements off `slice`, leaving the remainder in place
+fn split_off<'a, T>(slice: &mut &'a mut [T], len: usize) -> &'a mut [T] {
+    let (head, tail) = std::mem::take(slice).split_at_mut(len);
+    *slice = tail;
+    head
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::math::primitives::Plane3d;
+    use bevy::math::{Quat, Vec2, Vec3, Vec4};
+    use bevy::prelude::MeshBuilder;
+    use bevy::render::mesh::{
+        Indices, Mesh, MeshVertexAttributeId, Meshable, PrimitiveTopology, VertexAttributeValues,
+    };
+    use bevy::render::render_asset::RenderAssetUsages;
+    use bevy::transform::components::Transform;
+
+    use super::combine_meshes;
+
+    /// Plane tilted 45° between +x and +y, with tangents along its surface in the xy plane
+    fn tilted_plane() -> Mesh {
+        let normal = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let mut mesh = Plane3d::new(normal, Vec2::splat(0.5)).mesh().build();
+        let tangent = Vec3::new(1.0, -1.0, 0.0).normalize().extend(1.0).to_array();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, vec![tangent; mesh.count_vertices()]);
+        mesh
     }
 
-    if use_tangents {
-        mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangets);
+    fn float3(mesh: &Mesh, attribute: impl Into<MeshVertexAttributeId>) -> Vec<Vec3> {
+        match mesh.attribute(attribute) {
+            Some(VertexAttributeValues::Float32x3(values)) => values.iter().copied().map(Vec3::from).collect(),
+            other => panic!("expected Float32x3 values, got {other:?}"),
+        }
     }
 
-    if use_uvs {
-        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    fn tangents(mesh: &Mesh) -> Vec<Vec4> {
+        match mesh.attribute(Mesh::ATTRIBUTE_TANGENT) {
+            Some(VertexAttributeValues::Float32x4(values)) => values.iter().copied().map(Vec4::from).collect(),
+            other => panic!("expected Float32x4 tangents, got {other:?}"),
+        }
     }
 
-    if use_colors {
-        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    fn indices(mesh: &Mesh) -> Vec<u32> {
+        match mesh.indices() {
+            Some(Indices::U32(indices)) => indices.clone(),
+            other => panic!("expected u32 indices, got {other:?}"),
+        }
     }
 
-    mesh.insert_indices(Indices::U32(indices));
+    /// Combines `source` under `transform` and checks every vertex against the expected normal and tangent
+    fn assert_transformed(source: Mesh, transform: Transform, normal: Vec3, tangent: Vec4) {
+        let source_positions = float3(&source, Mesh::ATTRIBUTE_POSITION);
+        let combined = combine_meshes(&[(source, transform)], true, true, false, false);
 
-    mesh
+        for (out, pos) in float3(&combined, Mesh::ATTRIBUTE_POSITION)
+            .iter()
+            .zip(&source_positions)
+        {
+            let expected = transform.transform_point(*pos);
+            assert!(out.abs_diff_eq(expected, 1e-5), "position {out} != {expected}");
+        }
+        for out in float3(&combined, Mesh::ATTRIBUTE_NORMAL) {
+            assert!(out.abs_diff_eq(normal, 1e-5), "normal {out} != {normal}");
+        }
+        for out in tangents(&combined) {
+            assert!(out.abs_diff_eq(tangent, 1e-5), "tangent {out} != {tangent}");
+        }
+    }
+
+    #[test]
+    fn rotated() {
+        let mut plane = Plane3d::default().mesh().build();
+        plane.insert_attribute(Mesh::ATTRIBUTE_TANGENT, vec![
+            [1.0, 0.0, 0.0, 1.0];
+            plane.count_vertices()
+        ]);
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+
+        assert_transformed(plane, transform, Vec3::NEG_X, Vec4::new(0.0, 1.0, 0.0, 1.0));
+
+        // One corner by hand: (0.5, 0, 0.5) turns into (0, 0.5, 0.5), then moves by the translation
+        let combined = combine_meshes(
+            &[(Plane3d::default().mesh().build(), transform)],
+            false,
+            false,
+            false,
+            false,
+        );
+        let corner = Vec3::new(1.0, 2.5, 3.5);
+        assert!(float3(&combined, Mesh::ATTRIBUTE_POSITION)
+            .iter()
+            .any(|pos| pos.abs_diff_eq(corner, 1e-5)));
+    }
+
+    #[test]
+    fn non_uniformly_scaled() {
+        // The inverse transpose of the scale halves the normal's x, where the model matrix would double it
+        let transform = Transform::from_scale(Vec3::new(2.0, 1.0, 1.0));
+        let normal = Vec3::new(1.0, 2.0, 0.0).normalize();
+        let tangent = Vec3::new(2.0, -1.0, 0.0).normalize().extend(1.0);
+
+        assert_transformed(tilted_plane(), transform, normal, tangent);
+    }
+
+    #[test]
+    fn mirrored() {
+        let transform = Transform::from_scale(Vec3::new(-1.0, 1.0, 1.0));
+        let normal = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        let tangent = Vec3::new(-1.0, -1.0, 0.0).normalize().extend(-1.0);
+
+        assert_transformed(tilted_plane(), transform, normal, tangent);
+    }
+
+    #[test]
+    fn u16_indices_are_offset() {
+        let mut plane = Plane3d::default().mesh().build();
+        plane.insert_indices(Indices::U16(vec![0, 2, 1, 0, 3, 2]));
+        let meshes = [(plane.clone(), Transform::IDENTITY), (plane, Transform::IDENTITY)];
+
+        let combined = combine_meshes(&meshes, false, false, false, false);
+
+        assert_eq!(indices(&combined), [0, 2, 1, 0, 3, 2, 4, 6, 5, 4, 7, 6]);
+    }
+
+    #[test]
+    fn non_indexed_meshes_get_sequential_indices() {
+        let mut triangle = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+        triangle.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [
+            0.0, 0.0, 1.0,
+        ]]);
+        let mut plane = Plane3d::default().mesh().build();
+        plane.insert_indices(Indices::U32(vec![0, 2, 1, 0, 3, 2]));
+        let meshes = [
+            (triangle.clone(), Transform::IDENTITY),
+            (plane, Transform::IDENTITY),
+            (triangle, Transform::IDENTITY),
+        ];
+
+        let combined = combine_meshes(&meshes, false, false, false, false);
+
+        assert_eq!(indices(&combined), [0, 1, 2, 3, 5, 4, 3, 6, 5, 7, 8, 9]);
+        assert_eq!(combined.count_vertices(), 10);
+    }
 }